@@ -2,7 +2,7 @@ use bluer::{l2cap::{SocketAddr, Socket, SeqPacket}, Address, AddressType, Result
 use std::time::Duration;
 use log::{info, error, debug};
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc};
+use tokio::sync::{Mutex, Notify, mpsc, oneshot};
 use tokio::task::JoinSet;
 use tokio::time::{sleep, Instant};
 use std::collections::HashMap;
@@ -11,11 +11,51 @@ use serde_json;
 use crate::devices::airpods::AirPodsInformation;
 use crate::devices::enums::{DeviceData, DeviceInformation, DeviceType};
 use crate::utils::get_devices_path;
+use crate::bluetooth::resolver;
+use crate::bluetooth::opack::{self, Value};
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive as _;
 
 const PSM: u16 = 0x1001;
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 const POLL_INTERVAL: Duration = Duration::from_millis(200);
 const HEADER_BYTES: [u8; 4] = [0x04, 0x00, 0x04, 0x00];
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+#[cfg(feature = "metrics")]
+fn record_opcode_handled(opcode: u8) {
+    crate::bluetooth::metrics::counters::record_opcode(opcode);
+}
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+fn record_opcode_handled(_opcode: u8) {}
+
+#[cfg(feature = "metrics")]
+fn record_parse_error() {
+    crate::bluetooth::metrics::counters::record_parse_error();
+}
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+fn record_parse_error() {}
+
+/// Treats an OPACK `Bool(true)` or any non-zero `Int` as truthy, for flags like
+/// `audioRoutingSetOwnershipToFalse` that devices have been seen send as either.
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Int(n) => *n != 0,
+        _ => false,
+    }
+}
+
+/// Builds the error `Capabilities`-gated senders return for an unsupported `feature`, instead
+/// of sending a command the connected device's generation is known not to understand.
+fn unsupported_error(feature: &str) -> Error {
+    Error::from(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("{} is not supported by this device", feature)))
+}
 
 pub mod opcodes {
     pub const SET_FEATURE_FLAGS: u8 = 0x4D;
@@ -46,7 +86,7 @@ pub struct ControlCommandStatus {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, ToPrimitive)]
 pub enum ControlCommandIdentifiers {
     MicMode = 0x01,
     ButtonSendMode = 0x05,
@@ -85,49 +125,6 @@ pub enum ControlCommandIdentifiers {
     OwnsConnection = 0x06,
 }
 
-impl ControlCommandIdentifiers {
-    fn from_u8(value: u8) -> Option<Self> {
-        match value {
-            0x01 => Some(Self::MicMode),
-            0x05 => Some(Self::ButtonSendMode),
-            0x12 => Some(Self::VoiceTrigger),
-            0x14 => Some(Self::SingleClickMode),
-            0x15 => Some(Self::DoubleClickMode),
-            0x16 => Some(Self::ClickHoldMode),
-            0x17 => Some(Self::DoubleClickInterval),
-            0x18 => Some(Self::ClickHoldInterval),
-            0x1A => Some(Self::ListeningModeConfigs),
-            0x1B => Some(Self::OneBudAncMode),
-            0x1C => Some(Self::CrownRotationDirection),
-            0x0D => Some(Self::ListeningMode),
-            0x1E => Some(Self::AutoAnswerMode),
-            0x1F => Some(Self::ChimeVolume),
-            0x23 => Some(Self::VolumeSwipeInterval),
-            0x24 => Some(Self::CallManagementConfig),
-            0x25 => Some(Self::VolumeSwipeMode),
-            0x26 => Some(Self::AdaptiveVolumeConfig),
-            0x27 => Some(Self::SoftwareMuteConfig),
-            0x28 => Some(Self::ConversationDetectConfig),
-            0x29 => Some(Self::Ssl),
-            0x2C => Some(Self::HearingAid),
-            0x2E => Some(Self::AutoAncStrength),
-            0x2F => Some(Self::HpsGainSwipe),
-            0x30 => Some(Self::HrmState),
-            0x31 => Some(Self::InCaseToneConfig),
-            0x32 => Some(Self::SiriMultitoneConfig),
-            0x33 => Some(Self::HearingAssistConfig),
-            0x34 => Some(Self::AllowOffOption),
-            0x39 => Some(Self::StemConfig),
-            0x35 => Some(Self::SleepDetectionConfig),
-            0x36 => Some(Self::AllowAutoConnect),
-            0x0A => Some(Self::EarDetectionConfig),
-            0x20 => Some(Self::AutomaticConnectionConfig),
-            0x06 => Some(Self::OwnsConnection),
-            _ => None,
-        }
-    }
-}
-
 impl std::fmt::Display for ControlCommandIdentifiers {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = match self {
@@ -171,25 +168,26 @@ impl std::fmt::Display for ControlCommandIdentifiers {
     }
 }
 
+impl ControlCommandIdentifiers {
+    /// Reverse-looks-up a friendly name (case-insensitive, `-`/`_`/` ` interchangeable)
+    /// against this enum's `Display` strings, e.g. "listening-mode" -> `ListeningMode`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized = name.trim().to_lowercase().replace(['-', '_'], " ");
+        (0u8..=u8::MAX).find_map(|raw| {
+            Self::from_u8(raw).filter(|identifier| identifier.to_string().to_lowercase() == normalized)
+        })
+    }
+}
+
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, FromPrimitive, ToPrimitive)]
 pub enum ProximityKeyType {
     Irk = 0x01,
     EncKey = 0x04,
 }
 
-impl ProximityKeyType {
-    fn from_u8(value: u8) -> Option<Self> {
-        match value {
-            0x01 => Some(Self::Irk),
-            0x04 => Some(Self::EncKey),
-            _ => None,
-        }
-    }
-}
-
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum StemPressType {
     SinglePress = 0x05,
     DoublePress = 0x06,
@@ -198,14 +196,14 @@ pub enum StemPressType {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum StemPressBudType {
     Left = 0x01,
     Right = 0x02,
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum AudioSourceType {
     None = 0x00,
     Call = 0x01,
@@ -213,7 +211,7 @@ pub enum AudioSourceType {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum BatteryComponent {
     Left = 4,
     Right = 2,
@@ -221,7 +219,7 @@ pub enum BatteryComponent {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum BatteryStatus {
     Charging = 1,
     NotCharging = 2,
@@ -229,7 +227,7 @@ pub enum BatteryStatus {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum EarDetectionStatus {
     InEar = 0x00,
     OutOfEar = 0x01,
@@ -237,17 +235,6 @@ pub enum EarDetectionStatus {
     Disconnected = 0x03
 }
 
-impl AudioSourceType {
-    fn from_u8(value: u8) -> Option<Self> {
-        match value {
-            0x00 => Some(Self::None),
-            0x01 => Some(Self::Call),
-            0x02 => Some(Self::Media),
-            _ => None,
-        }
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AudioSource {
     pub mac: String,
@@ -269,6 +256,57 @@ pub struct ConnectedDevice {
     pub r#type: Option<String>,
 }
 
+/// Feature support inferred from the `model_number`/`hardware_revision` strings the AirPods
+/// report in their `INFORMATION` response. The model-number table below is assembled from
+/// publicly documented AirPods model numbers, not from anything the protocol itself declares,
+/// so it fails open: anything not listed is assumed to support everything, the same as the
+/// fixed `0xFF` feature-flags mask this replaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub adaptive_volume: bool,
+    pub smart_routing: bool,
+}
+
+impl Capabilities {
+    /// Assumes every feature is supported; the default until an `INFORMATION` response says
+    /// otherwise, and the fallback for models this table doesn't recognize.
+    fn permissive() -> Self {
+        Capabilities { adaptive_volume: true, smart_routing: true }
+    }
+
+    /// Looks `info.model_number` up against known first- and second-generation AirPods, which
+    /// predate Adaptive Audio (AirPods Pro 2) and the cross-device Smart Routing hand-off flow.
+    pub fn from_information(info: &AirPodsInformation) -> Self {
+        match info.model_number.as_str() {
+            "A1523" | "A1524" | "A2031" | "A2032" => {
+                Capabilities { adaptive_volume: false, smart_routing: false }
+            }
+            _ => Capabilities::permissive(),
+        }
+    }
+
+    /// Packs these capabilities into the byte mask `send_set_feature_flags_packet` sends,
+    /// rather than the blanket `0xFF` it used to hardcode. `0xD7` is the baseline the code used
+    /// before someone noticed flipping bits 3 and 5 on (getting to `0xFF`) turned adaptive
+    /// volume on, so those two bits are the ones gated on `adaptive_volume` here.
+    const BASELINE_MASK: u8 = 0xD7;
+    const ADAPTIVE_VOLUME_BITS: u8 = 0x28;
+
+    fn feature_flags_mask(&self) -> u8 {
+        if self.adaptive_volume {
+            Self::BASELINE_MASK | Self::ADAPTIVE_VOLUME_BITS
+        } else {
+            Self::BASELINE_MASK
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::permissive()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum AACPEvent {
     BatteryInfo(Vec<BatteryInfo>),
@@ -279,6 +317,21 @@ pub enum AACPEvent {
     AudioSource(AudioSource),
     ConnectedDevices(Vec<ConnectedDevice>, Vec<ConnectedDevice>),
     OwnershipToFalseRequest,
+    ConnectionState(ConnectionState),
+    /// A byte that didn't decode to any known value of a protocol enum, surfaced so a UI
+    /// layer can flag firmware/opcodes librepods doesn't understand yet.
+    UnknownValue { context: &'static str, value: u8 },
+}
+
+/// Lifecycle state of the L2CAP link to the AirPods, driven by the connect supervisor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    /// `attempt` counts consecutive failed-or-dropped connections since the last `Connected`,
+    /// starting at 1, so a UI layer can show e.g. "reconnecting (attempt 3)".
+    Reconnecting { attempt: u32 },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -287,6 +340,37 @@ pub struct AirPodsLEKeys {
     pub enc_key: String,
 }
 
+impl AirPodsLEKeys {
+    /// Returns whether `addr`, a 6-byte BLE random address taken from a scan advertisement,
+    /// resolves against the stored IRK, or `None` if no (valid) IRK has been collected yet.
+    pub fn resolves_address(&self, addr: &[u8; 6]) -> Option<bool> {
+        let irk: [u8; 16] = hex::decode(&self.irk).ok()?.try_into().ok()?;
+        Some(resolver::resolves(addr, &irk))
+    }
+}
+
+/// Notification sent to a registered suspend callback around a system sleep/wake cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspendEvent {
+    SuspendImminent,
+    ResumeComplete,
+}
+
+/// Snapshot of the bits of `AACPManagerState` that are meaningful to a UI layer while the
+/// L2CAP link is torn down for suspend, so it can keep showing the last-known state instead
+/// of blanking out.
+#[derive(Debug, Clone)]
+pub struct SuspendSnapshot {
+    pub battery_info: Vec<BatteryInfo>,
+    pub ear_detection_status: Vec<EarDetectionStatus>,
+    pub owns: bool,
+    pub connected_devices: Vec<ConnectedDevice>,
+}
+
+/// Resolves a pending `request_proximity_keys` call when the matching `PROXIMITY_KEYS_RSP`
+/// arrives; see `AACPManagerState::pending_proximity_keys`.
+type ProximityKeysResponder = oneshot::Sender<Vec<(u8, Vec<u8>)>>;
+
 pub struct AACPManagerState {
     pub sender: Option<mpsc::Sender<Vec<u8>>>,
     pub control_command_status_list: Vec<ControlCommandStatus>,
@@ -299,12 +383,31 @@ pub struct AACPManagerState {
     pub conversational_awareness_status: u8,
     pub old_ear_detection_status: Vec<EarDetectionStatus>,
     pub ear_detection_status: Vec<EarDetectionStatus>,
-    event_tx: Option<mpsc::UnboundedSender<AACPEvent>>,
+    event_tx: Vec<mpsc::UnboundedSender<AACPEvent>>,
     pub devices: HashMap<String, DeviceData>,
     pub airpods_mac: Option<Address>,
+    suspend_callbacks: HashMap<u64, mpsc::UnboundedSender<SuspendEvent>>,
+    next_suspend_callback_id: u64,
+    pub suspend_snapshot: Option<SuspendSnapshot>,
+    pub connection_state: ConnectionState,
+    /// When the link most recently transitioned to `ConnectionState::Connected`, so uptime can
+    /// be measured from the connection itself rather than from whenever a consumer (e.g. the
+    /// metrics exporter) happened to start watching it.
+    pub connected_at: Option<Instant>,
+    reconnect_notify: Option<Arc<Notify>>,
+    pending_proximity_keys: Option<ProximityKeysResponder>,
+    pub capabilities: Capabilities,
 }
 
 impl AACPManagerState {
+    /// Fans `event` out to every registered subscriber, dropping any whose receiving end has
+    /// since been closed instead of leaving them in `event_tx` forever — every reconnect of the
+    /// console/frb/audio-router subscribers calls `set_event_channel` again, so without this the
+    /// list would grow by one dead sender per reconnect for the life of the process.
+    fn emit_event(&mut self, event: AACPEvent) {
+        self.event_tx.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
     fn new() -> Self {
         let devices: HashMap<String, DeviceData> =
             std::fs::read_to_string(get_devices_path())
@@ -323,9 +426,17 @@ impl AACPManagerState {
             conversational_awareness_status: 0,
             old_ear_detection_status: Vec::new(),
             ear_detection_status: Vec::new(),
-            event_tx: None,
+            event_tx: Vec::new(),
             devices,
             airpods_mac: None,
+            suspend_callbacks: HashMap::new(),
+            next_suspend_callback_id: 0,
+            suspend_snapshot: None,
+            connection_state: ConnectionState::Disconnected,
+            connected_at: None,
+            reconnect_notify: None,
+            pending_proximity_keys: None,
+            capabilities: Capabilities::permissive(),
         }
     }
 }
@@ -344,32 +455,89 @@ impl AACPManager {
         }
     }
 
+    /// Connects to `addr` and keeps the link alive for as long as `disconnect()` isn't
+    /// called: a supervisor task retries failed or dropped connections with exponential
+    /// backoff, capped at `MAX_RECONNECT_BACKOFF` and reset after every successful connect.
     pub async fn connect(&mut self, addr: Address) {
-        info!("AACPManager connecting to {} on PSM {:#06X}...", addr, PSM);
-        let target_sa = SocketAddr::new(addr, AddressType::BrEdr, PSM);
+        {
+            let state = self.state.lock().await;
+            if state.airpods_mac == Some(addr) && !matches!(state.connection_state, ConnectionState::Disconnected) {
+                debug!("Already connected/connecting to {}, ignoring duplicate connect()", addr);
+                return;
+            }
+        }
+
+        // Stop whatever supervisor (if any) is running for a previous connect before starting
+        // a new one, so the two never race each other over `state.sender`/`reconnect_notify`.
+        let mut tasks = self.tasks.lock().await;
+        tasks.abort_all();
+        while tasks.join_next().await.is_some() {}
 
         {
             let mut state = self.state.lock().await;
             state.airpods_mac = Some(addr);
         }
+        self.set_connection_state(ConnectionState::Connecting).await;
 
-        let socket = match Socket::new_seq_packet() {
-            Ok(s) => s,
-            Err(e) => {
-                error!("Failed to create L2CAP socket: {}", e);
-                return;
-            }
+        let manager = self.clone();
+        tasks.spawn(connect_supervisor(manager, addr));
+    }
+
+    /// Tears down the link and stops the connect supervisor for good. Unlike a dropped
+    /// connection, `airpods_mac` is cleared so the supervisor won't retry.
+    pub async fn disconnect(&mut self) {
+        info!("Disconnecting AACPManager from {:?}", { self.state.lock().await.airpods_mac });
+        {
+            let mut state = self.state.lock().await;
+            state.airpods_mac = None;
+            state.sender = None;
+            state.reconnect_notify = None;
+            // Dropping this wakes anyone awaiting the matching response with an error instead
+            // of leaving them hanging forever now that no `PROXIMITY_KEYS_RSP` will ever arrive.
+            state.pending_proximity_keys = None;
+        }
+
+        let mut tasks = self.tasks.lock().await;
+        tasks.abort_all();
+        while tasks.join_next().await.is_some() {}
+        drop(tasks);
+
+        self.set_connection_state(ConnectionState::Disconnected).await;
+    }
+
+    async fn set_connection_state(&self, new_state: ConnectionState) {
+        let mut state = self.state.lock().await;
+        state.connection_state = new_state;
+        state.connected_at = match new_state {
+            ConnectionState::Connected => Some(Instant::now()),
+            _ => None,
         };
+        state.emit_event(AACPEvent::ConnectionState(new_state));
+    }
+
+    /// Performs a single L2CAP connect attempt and, on success, spawns the recv/send tasks
+    /// and returns the `Notify` that they'll signal when the link drops.
+    async fn establish_connection(&self, addr: Address) -> Result<Arc<Notify>> {
+        info!("AACPManager connecting to {} on PSM {:#06X}...", addr, PSM);
+        let target_sa = SocketAddr::new(addr, AddressType::BrEdr, PSM);
+
+        let socket = Socket::new_seq_packet().map_err(|e| {
+            error!("Failed to create L2CAP socket: {}", e);
+            e
+        })?;
 
         let seq_packet = match tokio::time::timeout(CONNECT_TIMEOUT, socket.connect(target_sa)).await {
             Ok(Ok(s)) => Arc::new(s),
             Ok(Err(e)) => {
                 error!("L2CAP connect failed: {}", e);
-                return;
+                return Err(e.into());
             }
             Err(_) => {
                 error!("L2CAP connect timed out");
-                return;
+                return Err(Error::from(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "L2CAP connect timed out",
+                )));
             }
         };
 
@@ -382,14 +550,17 @@ impl AACPManager {
                 Err(e) => {
                     if e.raw_os_error() == Some(107) { // ENOTCONN
                         error!("Peer has disconnected during connection setup.");
-                        return;
+                        return Err(e.into());
                     }
                     error!("Error getting peer address: {}", e);
                 }
             }
             if start.elapsed() >= CONNECT_TIMEOUT {
                 error!("Timed out waiting for L2CAP connection to be fully established.");
-                return;
+                return Err(Error::from(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "Timed out waiting for L2CAP connection to be fully established",
+                )));
             }
             sleep(POLL_INTERVAL).await;
         }
@@ -397,16 +568,95 @@ impl AACPManager {
         info!("L2CAP connection established with {}", addr);
 
         let (tx, rx) = mpsc::channel(128);
+        let notify = Arc::new(Notify::new());
 
-        let manager_clone = self.clone();
         {
             let mut state = self.state.lock().await;
             state.sender = Some(tx);
+            state.reconnect_notify = Some(notify.clone());
         }
 
         let mut tasks = self.tasks.lock().await;
-        tasks.spawn(recv_thread(manager_clone, seq_packet.clone()));
-        tasks.spawn(send_thread(rx, seq_packet));
+        tasks.spawn(recv_thread(self.clone(), seq_packet.clone()));
+        tasks.spawn(send_thread(self.clone(), rx, seq_packet));
+
+        Ok(notify)
+    }
+
+    /// Registers a callback to be notified of suspend/resume lifecycle transitions and
+    /// returns a callback id that can be used to correlate log output.
+    pub async fn register_suspend_callback(&self, tx: mpsc::UnboundedSender<SuspendEvent>) -> u64 {
+        let mut state = self.state.lock().await;
+        let id = state.next_suspend_callback_id;
+        state.next_suspend_callback_id += 1;
+        state.suspend_callbacks.insert(id, tx);
+        id
+    }
+
+    /// Called by the system power-management hook just before the machine suspends.
+    ///
+    /// Notifies suspend callbacks, snapshots the last-known device state so a UI layer can
+    /// keep displaying it while disconnected, aborts the recv/send tasks, and drops the
+    /// socket sender so any in-flight sends fail fast instead of blocking.
+    pub async fn prepare_suspend(&self, suspend_id: u64) {
+        info!("Preparing for suspend (id={})", suspend_id);
+
+        {
+            let state = self.state.lock().await;
+            for tx in state.suspend_callbacks.values() {
+                let _ = tx.send(SuspendEvent::SuspendImminent);
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        state.suspend_snapshot = Some(SuspendSnapshot {
+            battery_info: state.battery_info.clone(),
+            ear_detection_status: state.ear_detection_status.clone(),
+            owns: state.owns,
+            connected_devices: state.connected_devices.clone(),
+        });
+        state.sender = None;
+        drop(state);
+
+        let mut tasks = self.tasks.lock().await;
+        tasks.abort_all();
+        while tasks.join_next().await.is_some() {}
+        drop(tasks);
+
+        // The aborted supervisor can't report the link as gone, so do it here; otherwise
+        // `resume`'s `connect` would see a live-looking state and return as a duplicate.
+        self.set_connection_state(ConnectionState::Disconnected).await;
+    }
+
+    /// Called by the system power-management hook once the machine has woken up.
+    ///
+    /// Re-runs the full connect handshake against the last-known `airpods_mac` and replays
+    /// `REQUEST_NOTIFICATIONS` / `SET_FEATURE_FLAGS` so the AirPods forget nothing about our
+    /// desired notification set, then notifies suspend callbacks that resume is complete.
+    pub async fn resume(&mut self) -> Result<()> {
+        let addr = {
+            let state = self.state.lock().await;
+            state.airpods_mac
+        };
+
+        if let Some(addr) = addr {
+            info!("Resuming AACPManager after suspend, reconnecting to {}", addr);
+            // `connect` just (re)starts `connect_supervisor`, which replays the handshake
+            // itself via `replay_handshake` once it actually re-establishes the socket; doing
+            // it again here would race it and fail with "sender is not available" before the
+            // supervisor has had a chance to connect.
+            self.connect(addr).await;
+        } else {
+            debug!("Resume called with no prior airpods_mac, nothing to reconnect.");
+        }
+
+        let mut state = self.state.lock().await;
+        state.suspend_snapshot = None;
+        for tx in state.suspend_callbacks.values() {
+            let _ = tx.send(SuspendEvent::ResumeComplete);
+        }
+
+        Ok(())
     }
 
     async fn send_packet(&self, data: &[u8]) -> Result<()> {
@@ -433,11 +683,20 @@ impl AACPManager {
         self.send_packet(&packet).await
     }
 
+    /// Registers another `AACPEvent` subscriber, alongside any already registered (e.g. the
+    /// console, the frb bridge, and the audio router can all hold their own feed at once)
+    /// rather than replacing a single slot.
     pub async fn set_event_channel(&self, tx: mpsc::UnboundedSender<AACPEvent>) {
         let mut state = self.state.lock().await;
-        state.event_tx = Some(tx);
+        state.event_tx.push(tx);
     }
-    
+
+    /// Capabilities inferred from the last `INFORMATION` response, or `Capabilities::permissive`
+    /// if none has arrived yet for the connected device.
+    pub async fn capabilities(&self) -> Capabilities {
+        self.state.lock().await.capabilities
+    }
+
     pub async fn subscribe_to_control_command(&self, identifier: ControlCommandIdentifiers, tx: mpsc::UnboundedSender<Vec<u8>>) {
         let mut state = self.state.lock().await;
         state.control_command_subscribers.entry(identifier).or_default().push(tx);
@@ -447,6 +706,23 @@ impl AACPManager {
         }
     }
 
+    /// Decodes `value` as `T` via its derived `FromPrimitive` impl. On failure, logs,
+    /// increments the parse-error counter, and surfaces the raw byte on the event channel
+    /// as `AACPEvent::UnknownValue` so every protocol enum reports unknown values the same
+    /// way instead of each call site growing its own error branch.
+    async fn decode_or_report<T: num_traits::FromPrimitive>(&self, context: &'static str, value: u8) -> Option<T> {
+        match T::from_u8(value) {
+            Some(decoded) => Some(decoded),
+            None => {
+                error!("Unknown {} value: {:#04x}", context, value);
+                record_parse_error();
+                let mut state = self.state.lock().await;
+                state.emit_event(AACPEvent::UnknownValue { context, value });
+                None
+            }
+        }
+    }
+
     pub async fn receive_packet(&self, packet: &[u8]) {
         if !packet.starts_with(&HEADER_BYTES) {
             debug!("Received packet does not start with expected header: {}", hex::encode(packet));
@@ -459,53 +735,47 @@ impl AACPManager {
 
         let opcode = packet[4];
         let payload = &packet[4..];
+        record_opcode_handled(opcode);
 
         match opcode {
             opcodes::BATTERY_INFO => {
                 if payload.len() < 3 {
                     error!("Battery Info packet too short: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let count = payload[2] as usize;
                 if payload.len() < 3 + count * 5 {
                     error!("Battery Info packet length mismatch: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let mut batteries = Vec::with_capacity(count);
                 for i in 0..count {
                     let base_index = 3 + i * 5;
-                    batteries.push(BatteryInfo {
-                        component: match payload[base_index] {
-                            0x02 => BatteryComponent::Right,
-                            0x04 => BatteryComponent::Left,
-                            0x08 => BatteryComponent::Case,
-                            _ => {
-                                error!("Unknown battery component: {:#04x}", payload[base_index]);
-                                continue;
-                            }
-                        },
-                        level: payload[base_index + 2],
-                        status: match payload[base_index + 3] {
-                            0x01 => BatteryStatus::Charging,
-                            0x02 => BatteryStatus::NotCharging,
-                            0x04 => BatteryStatus::Disconnected,
-                            _ => {
-                                error!("Unknown battery status: {:#04x}", payload[base_index + 3]);
-                                continue;
-                            }
-                        }
-                    });
+                    let Some(component) = self
+                        .decode_or_report::<BatteryComponent>("battery component", payload[base_index])
+                        .await
+                    else {
+                        continue;
+                    };
+                    let Some(status) = self
+                        .decode_or_report::<BatteryStatus>("battery status", payload[base_index + 3])
+                        .await
+                    else {
+                        continue;
+                    };
+                    batteries.push(BatteryInfo { component, level: payload[base_index + 2], status });
                 }
                 let mut state = self.state.lock().await;
                 state.battery_info = batteries.clone();
-                if let Some(ref tx) = state.event_tx {
-                    let _ = tx.send(AACPEvent::BatteryInfo(batteries));
-                }
+                state.emit_event(AACPEvent::BatteryInfo(batteries));
                 info!("Received Battery Info: {:?}", state.battery_info);
             }
             opcodes::CONTROL_COMMAND => {
                 if payload.len() < 7 {
                     error!("Control Command packet too short: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let identifier_byte = payload[2];
@@ -517,7 +787,10 @@ impl AACPManager {
                     None => vec![0],
                 };
 
-                if let Some(identifier) = ControlCommandIdentifiers::from_u8(identifier_byte) {
+                if let Some(identifier) = self
+                    .decode_or_report::<ControlCommandIdentifiers>("control command identifier", identifier_byte)
+                    .await
+                {
                     let status = ControlCommandStatus { identifier, value: value.clone() };
                     let mut state = self.state.lock().await;
                     if let Some(existing) = state.control_command_status_list.iter_mut().find(|s| s.identifier == identifier) {
@@ -533,46 +806,34 @@ impl AACPManager {
                             let _ = sub.send(value.clone());
                         }
                     }
-                    if let Some(ref tx) = state.event_tx {
-                        let _ = tx.send(AACPEvent::ControlCommand(status));
-                    }
+                    state.emit_event(AACPEvent::ControlCommand(status));
                     info!("Received Control Command: {:?}, value: {}", identifier, hex::encode(&value));
-                } else {
-                    error!("Unknown Control Command identifier: {:#04x}", identifier_byte);
                 }
             }
             opcodes::EAR_DETECTION => {
-                let primary_status = packet[6];
-                let secondary_status = packet[7];
-                let mut statuses = Vec::new();
-                statuses.push(match primary_status {
-                    0x00 => EarDetectionStatus::InEar,
-                    0x01 => EarDetectionStatus::OutOfEar,
-                    0x02 => EarDetectionStatus::InCase,
-                    0x03 => EarDetectionStatus::Disconnected,
-                    _ => {
-                        error!("Unknown ear detection status: {:#04x}", primary_status);
-                        EarDetectionStatus::OutOfEar
-                    }
-                });
-                statuses.push(match secondary_status {
-                    0x00 => EarDetectionStatus::InEar,
-                    0x01 => EarDetectionStatus::OutOfEar,
-                    0x02 => EarDetectionStatus::InCase,
-                    0x03 => EarDetectionStatus::Disconnected,
-                    _ => {
-                        error!("Unknown ear detection status: {:#04x}", secondary_status);
-                        EarDetectionStatus::OutOfEar
-                    }
-                });
+                if payload.len() < 4 {
+                    error!("Ear Detection packet too short: {}", hex::encode(payload));
+                    record_parse_error();
+                    return;
+                }
+                let primary_status = self
+                    .decode_or_report::<EarDetectionStatus>("ear detection status", packet[6])
+                    .await
+                    .unwrap_or(EarDetectionStatus::OutOfEar);
+                let secondary_status = self
+                    .decode_or_report::<EarDetectionStatus>("ear detection status", packet[7])
+                    .await
+                    .unwrap_or(EarDetectionStatus::OutOfEar);
+                let statuses = vec![primary_status, secondary_status];
                 let mut state = self.state.lock().await;
                 state.old_ear_detection_status = state.ear_detection_status.clone();
                 state.ear_detection_status = statuses.clone();
                 
-                if let Some(ref tx) = state.event_tx {
+                if !state.event_tx.is_empty() {
                     debug!("Sending Ear Detection event: old: {:?}, new: {:?}", state.old_ear_detection_status, statuses);
-                    let _ = tx.send(AACPEvent::EarDetection(state.old_ear_detection_status.clone(), statuses));
                 }
+                let old_ear_detection_status = state.old_ear_detection_status.clone();
+                state.emit_event(AACPEvent::EarDetection(old_ear_detection_status, statuses));
                 info!("Received Ear Detection Status: {:?}", state.ear_detection_status);
             }
             opcodes::CONVERSATION_AWARENESS => {
@@ -580,9 +841,7 @@ impl AACPManager {
                     let status = packet[9];
                     let mut state = self.state.lock().await;
                     state.conversational_awareness_status = status;
-                    if let Some(ref tx) = state.event_tx {
-                        let _ = tx.send(AACPEvent::ConversationalAwareness(status));
-                    }
+                    state.emit_event(AACPEvent::ConversationalAwareness(status));
                     info!("Received Conversation Awareness: {}", status);
                 } else {
                     info!("Received Conversation Awareness packet with unexpected length: {}", packet.len());
@@ -591,6 +850,7 @@ impl AACPManager {
             opcodes::INFORMATION => {
                 if payload.len() < 6 {
                     error!("Information packet too short: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let data = &payload[4..];
@@ -634,6 +894,7 @@ impl AACPManager {
                     },
                 };
                 let mut state = self.state.lock().await;
+                state.capabilities = Capabilities::from_information(&info);
                 if let Some(mac) = state.airpods_mac {
                     if let Some(device_data) = state.devices.get_mut(&mac.to_string()) {
                         device_data.name = info.name.clone();
@@ -656,6 +917,7 @@ impl AACPManager {
             opcodes::PROXIMITY_KEYS_RSP => {
                 if payload.len() < 4 {
                     error!("Proximity Keys Response packet too short: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let key_count = payload[2] as usize;
@@ -665,6 +927,7 @@ impl AACPManager {
                 for _ in 0..key_count {
                     if offset + 3 >= payload.len() {
                         error!("Proximity Keys Response packet too short while parsing keys: {}", hex::encode(payload));
+                        record_parse_error();
                         return;
                     }
                     let key_type = payload[offset];
@@ -672,6 +935,7 @@ impl AACPManager {
                     offset += 4;
                     if offset + key_length > payload.len() {
                         error!("Proximity Keys Response packet too short for key data: {}", hex::encode(payload));
+                        record_parse_error();
                         return;
                     }
                     let key_data = payload[offset..offset + key_length].to_vec();
@@ -680,6 +944,9 @@ impl AACPManager {
                 }
                 info!("Received Proximity Keys Response: {:?}", keys.iter().map(|(kt, kd)| (kt, hex::encode(kd))).collect::<Vec<_>>());
                 let mut state = self.state.lock().await;
+                if let Some(respond_to) = state.pending_proximity_keys.take() {
+                    let _ = respond_to.send(keys.clone());
+                }
                 for (key_type, key_data) in &keys {
                     if let Some(kt) = ProximityKeyType::from_u8(*key_type) {
                         if let Some(mac) = state.airpods_mac {
@@ -697,6 +964,7 @@ impl AACPManager {
                                         }
                                         _ => {
                                             error!("Device information is not AirPods for adding LE IRK.");
+                                            record_parse_error();
                                         }
                                     }
                                 }
@@ -707,6 +975,7 @@ impl AACPManager {
                                         }
                                         _ => {
                                             error!("Device information is not AirPods for adding LE encryption key.");
+                                            record_parse_error();
                                         }
                                     }
                                 }
@@ -729,29 +998,33 @@ impl AACPManager {
             opcodes::AUDIO_SOURCE => {
                 if payload.len() < 9 {
                     error!("Audio Source packet too short: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let mac = format!(
                     "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
                     payload[7], payload[6], payload[5], payload[4], payload[3], payload[2]
                 );
-                let typ = AudioSourceType::from_u8(payload[8]).unwrap_or(AudioSourceType::None);
+                let typ = self
+                    .decode_or_report::<AudioSourceType>("audio source type", payload[8])
+                    .await
+                    .unwrap_or(AudioSourceType::None);
                 let audio_source = AudioSource { mac, r#type: typ };
                 let mut state = self.state.lock().await;
                 state.audio_source = Some(audio_source.clone());
-                if let Some(ref tx) = state.event_tx {
-                    let _ = tx.send(AACPEvent::AudioSource(audio_source));
-                }
+                state.emit_event(AACPEvent::AudioSource(audio_source));
                 info!("Received Audio Source: {:?}", state.audio_source);
             }
             opcodes::CONNECTED_DEVICES => {
                 if payload.len() < 3 {
                     error!("Connected Devices packet too short: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let count = payload[2] as usize;
                 if payload.len() < 3 + count * 8 {
                     error!("Connected Devices packet length mismatch: {}", hex::encode(payload));
+                    record_parse_error();
                     return;
                 }
                 let mut devices = Vec::with_capacity(count);
@@ -768,18 +1041,32 @@ impl AACPManager {
                 let mut state = self.state.lock().await;
                 state.old_connected_devices = state.connected_devices.clone();
                 state.connected_devices = devices.clone();
-                if let Some(ref tx) = state.event_tx {
-                    let _ = tx.send(AACPEvent::ConnectedDevices(state.old_connected_devices.clone(), devices));
-                }
+                let old_connected_devices = state.old_connected_devices.clone();
+                state.emit_event(AACPEvent::ConnectedDevices(old_connected_devices, devices));
                 info!("Received Connected Devices: {:?}", state.connected_devices);
             }
             opcodes::SMART_ROUTING_RESP => {
-                let packet_string = String::from_utf8_lossy(&payload[2..]);
-                info!("Received Smart Routing Response: {}", packet_string);
-                if packet_string.contains("SetOwnershipToFalse") {
-                    info!("Received OwnershipToFalse request");
-                    if let Some(ref tx) = self.state.lock().await.event_tx {
-                        let _ = tx.send(AACPEvent::OwnershipToFalseRequest);
+                if payload.len() < 3 {
+                    error!("Smart Routing Response packet too short: {}", hex::encode(payload));
+                    record_parse_error();
+                    return;
+                }
+                let body = payload[2..].strip_prefix(&[0x01]).unwrap_or(&payload[2..]);
+                match opack::decode(body) {
+                    Some(value) => {
+                        info!("Received Smart Routing Response: {:?}", value);
+                        let wants_ownership_false = value
+                            .get("audioRoutingSetOwnershipToFalse")
+                            .or_else(|| value.get("SetOwnershipToFalse"))
+                            .is_some_and(is_truthy);
+                        if wants_ownership_false {
+                            info!("Received OwnershipToFalse request");
+                            self.state.lock().await.emit_event(AACPEvent::OwnershipToFalseRequest);
+                        }
+                    }
+                    None => {
+                        debug!("Smart Routing Response did not decode as OPACK: {}", hex::encode(body));
+                        record_parse_error();
                     }
                 }
             }
@@ -799,8 +1086,8 @@ impl AACPManager {
 
     pub async fn send_set_feature_flags_packet(&self) -> Result<()> {
         let opcode = [opcodes::SET_FEATURE_FLAGS, 0x00];
-        // let data = [0xD7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
-        let data = [0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]; // adaptive volume is actually useful, seeing if it works
+        let mask = self.capabilities().await.feature_flags_mask();
+        let data = [mask, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
         let packet = [opcode.as_slice(), data.as_slice()].concat();
         self.send_data_packet(&packet).await
     }
@@ -824,6 +1111,22 @@ impl AACPManager {
         self.send_data_packet(&packet).await
     }
 
+    /// Sends `PROXIMITY_KEYS_REQ` and registers `respond_to` to be fulfilled by
+    /// `receive_packet` when the matching `PROXIMITY_KEYS_RSP` arrives, turning the
+    /// request/response exchange into a single awaitable call for peer-style callers
+    /// (see `bluetooth::command`).
+    pub async fn request_proximity_keys(
+        &self,
+        key_types: Vec<ProximityKeyType>,
+        respond_to: ProximityKeysResponder,
+    ) -> Result<()> {
+        {
+            let mut state = self.state.lock().await;
+            state.pending_proximity_keys = Some(respond_to);
+        }
+        self.send_proximity_keys_request(key_types).await
+    }
+
     pub async fn send_rename_packet(&self, name: &str) -> Result<()> {
         let name_bytes = name.as_bytes();
         let size = name_bytes.len();
@@ -837,6 +1140,9 @@ impl AACPManager {
     }
     
     pub async fn send_control_command(&self, identifier: ControlCommandIdentifiers, value: &[u8]) -> Result<()> {
+        if identifier == ControlCommandIdentifiers::AdaptiveVolumeConfig && !self.capabilities().await.adaptive_volume {
+            return Err(unsupported_error("Adaptive Volume Config"));
+        }
         let opcode = [opcodes::CONTROL_COMMAND, 0x00];
         let mut data = vec![identifier as u8];
         for i in 0..4 {
@@ -846,186 +1152,97 @@ impl AACPManager {
         self.send_data_packet(&packet).await
     }
 
-    pub async fn send_media_information_new_device(&self, self_mac_address: &str, target_mac_address: &str) -> Result<()> {
+    /// Builds and sends a `SMART_ROUTING` packet carrying `dict` OPACK-encoded: target MAC
+    /// (reversed), a little-endian byte length, a leading protocol-version byte, then the
+    /// encoded dictionary. Replaces the hand-built, fixed-length-padded buffers the
+    /// `send_*` methods below used to assemble byte by byte.
+    async fn send_smart_routing_dict(&self, target_mac_address: &str, dict: Value) -> Result<()> {
+        if !self.capabilities().await.smart_routing {
+            return Err(unsupported_error("Smart Routing"));
+        }
         let opcode = [opcodes::SMART_ROUTING, 0x00];
-        let mut buffer = Vec::with_capacity(112);
         let target_mac_bytes: Vec<u8> = target_mac_address.split(':').map(|s| u8::from_str_radix(s, 16).unwrap()).collect();
-        buffer.extend_from_slice(&target_mac_bytes.iter().rev().cloned().collect::<Vec<u8>>());
-
-        buffer.extend_from_slice(&[0x68, 0x00]);
-        buffer.extend_from_slice(&[0x01, 0xE5, 0x4A]);
-        buffer.extend_from_slice(b"playingApp");
-        buffer.push(0x42);
-        buffer.extend_from_slice(b"NA");
-        buffer.push(0x52);
-        buffer.extend_from_slice(b"hostStreamingState");
-        buffer.push(0x42);
-        buffer.extend_from_slice(b"NO");
-        buffer.push(0x49);
-        buffer.extend_from_slice(b"btAddress");
-        buffer.push(0x51);
-        buffer.extend_from_slice(self_mac_address.as_bytes());
-        buffer.push(0x46);
-        buffer.extend_from_slice(b"btName");
-        buffer.push(0x43);
-        buffer.extend_from_slice(b"Mac");
-        buffer.push(0x58);
-        buffer.extend_from_slice(b"otherDevice");
-        buffer.extend_from_slice(b"AudioCategory");
-        buffer.extend_from_slice(&[0x30, 0x64]);
+
+        let mut body = vec![0x01]; // protocol version
+        body.extend_from_slice(&opack::encode(&dict));
+
+        let mut buffer = Vec::with_capacity(6 + 2 + body.len());
+        buffer.extend(target_mac_bytes.iter().rev());
+        buffer.extend_from_slice(&(body.len() as u16).to_le_bytes());
+        buffer.extend_from_slice(&body);
 
         let packet = [opcode.as_slice(), buffer.as_slice()].concat();
         self.send_data_packet(&packet).await
     }
 
-    pub async fn send_hijack_request(&self, target_mac_address: &str) -> Result<()> {
-        let opcode = [opcodes::SMART_ROUTING, 0x00];
-        let mut buffer = Vec::with_capacity(106);
-        let target_mac_bytes: Vec<u8> = target_mac_address.split(':').map(|s| u8::from_str_radix(s, 16).unwrap()).collect();
-        buffer.extend_from_slice(&target_mac_bytes.iter().rev().cloned().collect::<Vec<u8>>());
-        buffer.extend_from_slice(&[0x62, 0x00]);
-        buffer.extend_from_slice(&[0x01, 0xE5]);
-        buffer.push(0x4A);
-        buffer.extend_from_slice(b"localscore");
-        buffer.extend_from_slice(&[0x30, 0x64]);
-        buffer.push(0x46);
-        buffer.extend_from_slice(b"reason");
-        buffer.push(0x48);
-        buffer.extend_from_slice(b"Hijackv2");
-        buffer.push(0x51);
-        buffer.extend_from_slice(b"audioRoutingScore");
-        buffer.extend_from_slice(&[0x31, 0x2D, 0x01, 0x5F]);
-        buffer.extend_from_slice(b"audioRoutingSetOwnershipToFalse");
-        buffer.push(0x01);
-        buffer.push(0x4B);
-        buffer.extend_from_slice(b"remotescore");
-        buffer.push(0xA5);
-
-        while buffer.len() < 106 {
-            buffer.push(0x00);
-        }
+    pub async fn send_media_information_new_device(&self, self_mac_address: &str, target_mac_address: &str) -> Result<()> {
+        let dict = Value::Dict(vec![
+            (Value::String("playingApp".to_string()), Value::String("NA".to_string())),
+            (Value::String("hostStreamingState".to_string()), Value::String("NO".to_string())),
+            (Value::String("btAddress".to_string()), Value::String(self_mac_address.to_string())),
+            (Value::String("btName".to_string()), Value::String("Mac".to_string())),
+            (Value::String("otherDevice".to_string()), Value::Bool(true)),
+            (Value::String("AudioCategory".to_string()), Value::Int(100)),
+        ]);
+        self.send_smart_routing_dict(target_mac_address, dict).await
+    }
 
-        let packet = [opcode.as_slice(), buffer.as_slice()].concat();
-        self.send_data_packet(&packet).await
+    pub async fn send_hijack_request(&self, target_mac_address: &str) -> Result<()> {
+        let dict = Value::Dict(vec![
+            (Value::String("localscore".to_string()), Value::Int(100)),
+            (Value::String("reason".to_string()), Value::String("Hijackv2".to_string())),
+            (Value::String("audioRoutingScore".to_string()), Value::Int(301)),
+            (Value::String("audioRoutingSetOwnershipToFalse".to_string()), Value::Bool(true)),
+            (Value::String("remotescore".to_string()), Value::Int(165)),
+        ]);
+        self.send_smart_routing_dict(target_mac_address, dict).await
     }
 
     pub async fn send_media_information(&self, self_mac_address: &str, target_mac_address: &str, streaming_state: bool) -> Result<()> {
-        let opcode = [opcodes::SMART_ROUTING, 0x00];
-        let mut buffer = Vec::with_capacity(138);
-        let target_mac_bytes: Vec<u8> = target_mac_address.split(':').map(|s| u8::from_str_radix(s, 16).unwrap()).collect();
-        buffer.extend_from_slice(&target_mac_bytes.iter().rev().cloned().collect::<Vec<u8>>());
-        buffer.extend_from_slice(&[0x82, 0x00]);
-        buffer.extend_from_slice(&[0x01, 0xE5, 0x4A]);
-        buffer.extend_from_slice(b"PlayingApp");
-        buffer.push(0x56);
-        buffer.extend_from_slice(b"com.google.ios.youtube");
-        buffer.push(0x52);
-        buffer.extend_from_slice(b"HostStreamingState");
-        buffer.push(0x42);
-        buffer.extend_from_slice(if streaming_state { b"YES" } else { b"NO" });
-        buffer.push(0x49);
-        buffer.extend_from_slice(b"btAddress");
-        buffer.push(0x51);
-        buffer.extend_from_slice(self_mac_address.as_bytes());
-        buffer.extend_from_slice(b"btName");
-        buffer.push(0x43);
-        buffer.extend_from_slice(b"Mac");
-        buffer.push(0x58);
-        buffer.extend_from_slice(b"otherDevice");
-        buffer.extend_from_slice(b"AudioCategory");
-        buffer.extend_from_slice(&[0x31, 0x2D, 0x01]);
-
-        while buffer.len() < 138 {
-            buffer.push(0x00);
-        }
-        let packet = [opcode.as_slice(), buffer.as_slice()].concat();
-        self.send_data_packet(&packet).await
+        let dict = Value::Dict(vec![
+            (Value::String("PlayingApp".to_string()), Value::String("com.google.ios.youtube".to_string())),
+            (
+                Value::String("HostStreamingState".to_string()),
+                Value::String(if streaming_state { "YES" } else { "NO" }.to_string()),
+            ),
+            (Value::String("btAddress".to_string()), Value::String(self_mac_address.to_string())),
+            (Value::String("btName".to_string()), Value::String("Mac".to_string())),
+            (Value::String("otherDevice".to_string()), Value::Bool(true)),
+            (Value::String("AudioCategory".to_string()), Value::Int(301)),
+        ]);
+        self.send_smart_routing_dict(target_mac_address, dict).await
     }
 
     pub async fn send_smart_routing_show_ui(&self, target_mac_address: &str) -> Result<()> {
-        let opcode = [opcodes::SMART_ROUTING, 0x00];
-        let mut buffer = Vec::with_capacity(134);
-        let target_mac_bytes: Vec<u8> = target_mac_address.split(':').map(|s| u8::from_str_radix(s, 16).unwrap()).collect();
-        buffer.extend_from_slice(&target_mac_bytes.iter().rev().cloned().collect::<Vec<u8>>());
-        buffer.extend_from_slice(&[0x7E, 0x00]);
-        buffer.extend_from_slice(&[0x01, 0xE6, 0x5B]);
-        buffer.extend_from_slice(b"SmartRoutingKeyShowNearbyUI");
-        buffer.push(0x01);
-        buffer.push(0x4A);
-        buffer.extend_from_slice(b"localscore");
-        buffer.extend_from_slice(&[0x31, 0x2D]);
-        buffer.push(0x01);
-        buffer.push(0x46);
-        buffer.extend_from_slice(b"reasonHhijackv2");
-        buffer.push(0x51);
-        buffer.extend_from_slice(b"audioRoutingScore");
-        buffer.push(0xA2);
-        buffer.push(0x5F);
-        buffer.extend_from_slice(b"audioRoutingSetOwnershipToFalse");
-        buffer.push(0x01);
-        buffer.push(0x4B);
-        buffer.extend_from_slice(b"remotescore");
-        buffer.push(0xA2);
-
-        while buffer.len() < 134 {
-            buffer.push(0x00);
-        }
-
-        let packet = [opcode.as_slice(), buffer.as_slice()].concat();
-        self.send_data_packet(&packet).await
+        let dict = Value::Dict(vec![
+            (Value::String("SmartRoutingKeyShowNearbyUI".to_string()), Value::Bool(true)),
+            (Value::String("localscore".to_string()), Value::Int(301)),
+            (Value::String("reason".to_string()), Value::String("Hijackv2".to_string())),
+            (Value::String("audioRoutingScore".to_string()), Value::Int(162)),
+            (Value::String("audioRoutingSetOwnershipToFalse".to_string()), Value::Bool(true)),
+            (Value::String("remotescore".to_string()), Value::Int(162)),
+        ]);
+        self.send_smart_routing_dict(target_mac_address, dict).await
     }
 
     pub async fn send_hijack_reversed(&self, target_mac_address: &str) -> Result<()> {
-        let opcode = [opcodes::SMART_ROUTING, 0x00];
-        let mut buffer = Vec::with_capacity(97);
-        let target_mac_bytes: Vec<u8> = target_mac_address.split(':').map(|s| u8::from_str_radix(s, 16).unwrap()).collect();
-        buffer.extend_from_slice(&target_mac_bytes.iter().rev().cloned().collect::<Vec<u8>>());
-        buffer.extend_from_slice(&[0x59, 0x00]);
-        buffer.extend_from_slice(&[0x01, 0xE3]);
-        buffer.push(0x5F);
-        buffer.extend_from_slice(b"audioRoutingSetOwnershipToFalse");
-        buffer.push(0x01);
-        buffer.push(0x59);
-        buffer.extend_from_slice(b"audioRoutingShowReverseUI");
-        buffer.push(0x01);
-        buffer.push(0x46);
-        buffer.extend_from_slice(b"reason");
-        buffer.push(0x53);
-        buffer.extend_from_slice(b"ReverseBannerTapped");
-
-        while buffer.len() < 97 {
-            buffer.push(0x00);
-        }
-
-        let packet = [opcode.as_slice(), buffer.as_slice()].concat();
-        self.send_data_packet(&packet).await
+        let dict = Value::Dict(vec![
+            (Value::String("audioRoutingSetOwnershipToFalse".to_string()), Value::Bool(true)),
+            (Value::String("audioRoutingShowReverseUI".to_string()), Value::Bool(true)),
+            (Value::String("reason".to_string()), Value::String("ReverseBannerTapped".to_string())),
+        ]);
+        self.send_smart_routing_dict(target_mac_address, dict).await
     }
 
     pub async fn send_add_tipi_device(&self, self_mac_address: &str, target_mac_address: &str) -> Result<()> {
-        let opcode = [opcodes::SMART_ROUTING, 0x00];
-        let mut buffer = Vec::with_capacity(86);
-        let target_mac_bytes: Vec<u8> = target_mac_address.split(':').map(|s| u8::from_str_radix(s, 16).unwrap()).collect();
-        buffer.extend_from_slice(&target_mac_bytes.iter().rev().cloned().collect::<Vec<u8>>());
-        buffer.extend_from_slice(&[0x4E, 0x00]);
-        buffer.extend_from_slice(&[0x01, 0xE5]);
-        buffer.extend_from_slice(&[0x48, 0x69]);
-        buffer.extend_from_slice(b"idleTime");
-        buffer.extend_from_slice(&[0x08, 0x47]);
-        buffer.extend_from_slice(b"newTipi");
-        buffer.extend_from_slice(&[0x01, 0x49]);
-        buffer.extend_from_slice(b"btAddress");
-        buffer.push(0x51);
-        buffer.extend_from_slice(self_mac_address.as_bytes());
-        buffer.push(0x46);
-        buffer.extend_from_slice(b"btName");
-        buffer.push(0x43);
-        buffer.extend_from_slice(b"Mac");
-        buffer.push(0x50);
-        buffer.extend_from_slice(b"nearbyAudioScore");
-        buffer.push(0x0E);
-
-        let packet = [opcode.as_slice(), buffer.as_slice()].concat();
-        self.send_data_packet(&packet).await
+        let dict = Value::Dict(vec![
+            (Value::String("idleTime".to_string()), Value::Int(8)),
+            (Value::String("newTipi".to_string()), Value::Bool(true)),
+            (Value::String("btAddress".to_string()), Value::String(self_mac_address.to_string())),
+            (Value::String("btName".to_string()), Value::String("Mac".to_string())),
+            (Value::String("nearbyAudioScore".to_string()), Value::Int(14)),
+        ]);
+        self.send_smart_routing_dict(target_mac_address, dict).await
     }
 
     pub async fn send_some_packet(&self) -> Result<()> {
@@ -1060,11 +1277,10 @@ async fn recv_thread(manager: AACPManager, sp: Arc<SeqPacket>) {
             }
         }
     }
-    let mut state = manager.state.lock().await;
-    state.sender = None;
+    notify_link_dropped(&manager).await;
 }
 
-async fn send_thread(mut rx: mpsc::Receiver<Vec<u8>>, sp: Arc<SeqPacket>) {
+async fn send_thread(manager: AACPManager, mut rx: mpsc::Receiver<Vec<u8>>, sp: Arc<SeqPacket>) {
     while let Some(data) = rx.recv().await {
         if let Err(e) = sp.send(&data).await {
             error!("Failed to send data: {}", e);
@@ -1073,4 +1289,121 @@ async fn send_thread(mut rx: mpsc::Receiver<Vec<u8>>, sp: Arc<SeqPacket>) {
         debug!("Sent {} bytes: {}", data.len(), hex::encode(&data));
     }
     info!("Send thread finished.");
+    notify_link_dropped(&manager).await;
+}
+
+/// Clears the sender and wakes the connect supervisor (if one is waiting) so it can start
+/// reconnecting. Called from whichever of recv/send notices the link died first; the other
+/// will find `reconnect_notify` already taken and do nothing.
+async fn notify_link_dropped(manager: &AACPManager) {
+    let mut state = manager.state.lock().await;
+    state.sender = None;
+    // Same reasoning as `disconnect`: a dropped link means the in-flight proximity keys
+    // request, if any, will never get its response.
+    state.pending_proximity_keys = None;
+    if let Some(notify) = state.reconnect_notify.take() {
+        notify.notify_one();
+    }
+}
+
+/// Re-runs the handshake and notification/feature-flag requests the AirPods expect after
+/// every (re)connect, then re-issues a proximity keys request so `le_keys` gets repopulated
+/// for the just-(re)connected device, mirroring what `resume()` already does after suspend.
+async fn replay_handshake(manager: &AACPManager) -> Result<()> {
+    manager.send_handshake().await?;
+    manager.send_notification_request().await?;
+    manager.send_set_feature_flags_packet().await?;
+    manager.send_proximity_keys_request(vec![ProximityKeyType::Irk, ProximityKeyType::EncKey]).await
+}
+
+/// Owns the connect/reconnect loop for a single `airpods_mac`. Retries with exponential
+/// backoff (reset on every successful connect) until `disconnect()` clears `airpods_mac` or
+/// the task is aborted (e.g. by `prepare_suspend`).
+async fn connect_supervisor(manager: AACPManager, addr: Address) {
+    let mut backoff = INITIAL_RECONNECT_BACKOFF;
+    let mut attempt: u32 = 0;
+
+    loop {
+        {
+            let state = manager.state.lock().await;
+            if state.airpods_mac != Some(addr) {
+                break;
+            }
+        }
+
+        match manager.establish_connection(addr).await {
+            Ok(notify) => {
+                backoff = INITIAL_RECONNECT_BACKOFF;
+                attempt = 0;
+                if let Err(e) = replay_handshake(&manager).await {
+                    error!("Handshake replay after connecting to {} failed: {}", addr, e);
+                }
+                manager.set_connection_state(ConnectionState::Connected).await;
+                notify.notified().await;
+
+                let state = manager.state.lock().await;
+                if state.airpods_mac != Some(addr) {
+                    break;
+                }
+                drop(state);
+                attempt += 1;
+                manager.set_connection_state(ConnectionState::Reconnecting { attempt }).await;
+            }
+            Err(e) => {
+                attempt += 1;
+                error!("Connect attempt to {} failed: {}, retrying in {:?}", addr, e, backoff);
+                manager.set_connection_state(ConnectionState::Reconnecting { attempt }).await;
+                sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+            }
+        }
+    }
+
+    manager.set_connection_state(ConnectionState::Disconnected).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn information_with_model(model_number: &str) -> AirPodsInformation {
+        AirPodsInformation {
+            name: String::new(),
+            model_number: model_number.to_string(),
+            manufacturer: String::new(),
+            serial_number: String::new(),
+            version1: String::new(),
+            version2: String::new(),
+            hardware_revision: String::new(),
+            updater_identifier: String::new(),
+            left_serial_number: String::new(),
+            right_serial_number: String::new(),
+            version3: String::new(),
+            le_keys: AirPodsLEKeys { irk: String::new(), enc_key: String::new() },
+        }
+    }
+
+    #[test]
+    fn permissive_mask_enables_adaptive_volume_bits() {
+        assert_eq!(Capabilities::permissive().feature_flags_mask(), 0xFF);
+    }
+
+    #[test]
+    fn mask_without_adaptive_volume_falls_back_to_baseline() {
+        let capabilities = Capabilities { adaptive_volume: false, smart_routing: true };
+        assert_eq!(capabilities.feature_flags_mask(), 0xD7);
+    }
+
+    #[test]
+    fn first_generation_airpods_lack_adaptive_volume_and_smart_routing() {
+        let capabilities = Capabilities::from_information(&information_with_model("A1523"));
+        assert!(!capabilities.adaptive_volume);
+        assert!(!capabilities.smart_routing);
+    }
+
+    #[test]
+    fn unrecognized_model_is_permissive() {
+        let capabilities = Capabilities::from_information(&information_with_model("A3028"));
+        assert_eq!(capabilities, Capabilities::permissive());
+    }
 }
@@ -0,0 +1,274 @@
+//! Optional OS audio-routing subsystem, enabled via the `audio-router` cargo feature.
+//!
+//! `AUDIO_SOURCE`/`CONNECTED_DEVICES` already tell us when the AirPods become (or stop being)
+//! the active sink; this module is the consumer that acts on it, rather than the packet
+//! parser reaching into OS audio APIs itself. `spawn_router` is started like
+//! `metrics::spawn_exporter` (from an `AACPManager`, returning the task's `JoinHandle`),
+//! registers its own event channel, and drives whatever `AudioRouter` backend the host
+//! platform enables.
+
+use std::sync::Arc;
+
+use log::info;
+use tokio::sync::mpsc;
+
+use super::aacp::{AACPEvent, AACPManager, AudioSourceType};
+
+/// Platform hook for switching the operating system's default audio output device.
+pub trait AudioRouter: Send + Sync {
+    /// Switches the system default output to the device identified by `mac` (the AirPods'
+    /// Bluetooth address). `spawn_router` passes an empty `mac` once the AirPods stop being
+    /// the active sink; the backends in this module then leave the current default output
+    /// as it is and do not pick another device.
+    fn set_default_output(&self, mac: &str);
+
+    /// Registers `on_change` to be invoked whenever the system's active default output
+    /// changes outside of this trait's own calls (unplugging, another app stealing the
+    /// sink), so callers can keep their own notion of ownership in sync.
+    fn on_device_change(&self, on_change: Box<dyn Fn(String) + Send + Sync>);
+}
+
+/// Subscribes to `manager`'s event channel and drives `router` from `AudioSource`/
+/// `ConnectedDevices` events until the manager is dropped or the returned task is aborted.
+pub fn spawn_router(manager: AACPManager, router: Arc<dyn AudioRouter>) -> tokio::task::JoinHandle<()> {
+    router.on_device_change(Box::new(|current| {
+        info!("System default output changed to \"{}\" outside of librepods", current);
+    }));
+
+    tokio::spawn(async move {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        manager.set_event_channel(tx).await;
+
+        while let Some(event) = rx.recv().await {
+            match event {
+                AACPEvent::AudioSource(source) => {
+                    let mac = if source.r#type == AudioSourceType::None { "" } else { source.mac.as_str() };
+                    router.set_default_output(mac);
+                }
+                AACPEvent::ConnectedDevices(_, current) if current.is_empty() => {
+                    router.set_default_output("");
+                }
+                _ => {}
+            }
+        }
+    })
+}
+
+/// Linux backend speaking the PulseAudio client protocol, which PipeWire also implements
+/// through its `pulseaudio` compatibility module — on the common case of PipeWire replacing
+/// PulseAudio outright, this reaches PipeWire without linking against `libpipewire` directly.
+#[cfg(all(target_os = "linux", feature = "linux-audio"))]
+mod linux {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    use libpulse_binding::context::subscribe::InterestMaskSet;
+    use libpulse_binding::context::{Context, FlagSet as ContextFlagSet, State as ContextState};
+    use libpulse_binding::mainloop::threaded::Mainloop;
+    use log::{debug, error};
+
+    use super::AudioRouter;
+
+    /// The `on_device_change` callback's storage type, passed into the thread once and then
+    /// shared between `Command::OnDeviceChange` and the subscribe callback it feeds.
+    type DeviceChangeCallback = Box<dyn Fn(String) + Send + Sync>;
+
+    /// `Mainloop`/`Context` wrap non-`Send` C state (`pa_threaded_mainloop`/`pa_context`), so
+    /// they're owned entirely by one dedicated thread; `PulseAudioRouter` just holds the
+    /// sending half of a command channel into it, the same actor shape `bluetooth::command`
+    /// uses for `AACPManager`.
+    enum Command {
+        SetDefaultOutput(String),
+        OnDeviceChange(DeviceChangeCallback),
+    }
+
+    pub struct PulseAudioRouter {
+        commands: Sender<Command>,
+    }
+
+    impl PulseAudioRouter {
+        /// Connects to the user's PulseAudio (or PipeWire-pulse) server and hands it off to a
+        /// dedicated thread that owns the connection for the rest of the process's life.
+        pub fn connect() -> Result<Self, String> {
+            let (tx, rx) = mpsc::channel();
+            let (ready_tx, ready_rx) = mpsc::channel();
+            std::thread::spawn(move || pulse_thread(rx, ready_tx));
+            ready_rx.recv().map_err(|_| "PulseAudio thread exited before starting".to_string())??;
+            Ok(PulseAudioRouter { commands: tx })
+        }
+    }
+
+    impl AudioRouter for PulseAudioRouter {
+        fn set_default_output(&self, mac: &str) {
+            let _ = self.commands.send(Command::SetDefaultOutput(mac.to_string()));
+        }
+
+        fn on_device_change(&self, on_change: Box<dyn Fn(String) + Send + Sync>) {
+            let _ = self.commands.send(Command::OnDeviceChange(on_change));
+        }
+    }
+
+    /// BlueZ (and PipeWire's BlueZ-compatible naming) publish Bluetooth A2DP sinks as
+    /// `bluez_sink.<MAC_WITH_UNDERSCORES>.a2dp_sink`, so the sink name can be built directly
+    /// without listing and matching every sink.
+    fn bluez_sink_name(mac: &str) -> String {
+        format!("bluez_sink.{}.a2dp_sink", mac.replace(':', "_").to_uppercase())
+    }
+
+    fn pulse_thread(commands: Receiver<Command>, ready: Sender<Result<(), String>>) {
+        let Some(mut mainloop) = Mainloop::new() else {
+            let _ = ready.send(Err("failed to create PulseAudio mainloop".to_string()));
+            return;
+        };
+        let Some(mut context) = Context::new(&mainloop, "librepods") else {
+            let _ = ready.send(Err("failed to create PulseAudio context".to_string()));
+            return;
+        };
+
+        if let Err(e) = context.connect(None, ContextFlagSet::NOFLAGS, None) {
+            let _ = ready.send(Err(format!("failed to connect to PulseAudio: {}", e)));
+            return;
+        }
+        if let Err(e) = mainloop.start() {
+            let _ = ready.send(Err(format!("failed to start PulseAudio mainloop: {}", e)));
+            return;
+        }
+        while !matches!(context.get_state(), ContextState::Ready) {
+            std::thread::yield_now();
+        }
+        let _ = ready.send(Ok(()));
+
+        let on_change: Rc<RefCell<Option<DeviceChangeCallback>>> = Rc::new(RefCell::new(None));
+        {
+            let on_change = on_change.clone();
+            // `Introspector` just refcounts the underlying context pointer rather than
+            // borrowing `context` itself, so it can be moved into this closure even though
+            // `context` is still used below (`subscribe`, `set_default_sink`, ...).
+            let introspect = context.introspect();
+            context.set_subscribe_callback(Some(Box::new(move |_facility, _op, _index| {
+                if on_change.borrow().is_none() {
+                    return;
+                }
+                let on_change = on_change.clone();
+                introspect.get_server_info(move |info| {
+                    if let Some(cb) = on_change.borrow().as_ref() {
+                        cb(info.default_sink_name.as_deref().unwrap_or_default().to_string());
+                    }
+                });
+            })));
+        }
+        context.subscribe(InterestMaskSet::SINK, |success| {
+            if !success {
+                error!("Failed to subscribe to PulseAudio sink change events");
+            }
+        });
+
+        for command in commands {
+            match command {
+                Command::SetDefaultOutput(mac) => {
+                    if mac.is_empty() {
+                        debug!("No AirPods sink to restore from; leaving the current default output alone");
+                        continue;
+                    }
+                    context.set_default_sink(&bluez_sink_name(&mac), |_| {});
+                }
+                Command::OnDeviceChange(cb) => {
+                    *on_change.borrow_mut() = Some(cb);
+                }
+            }
+        }
+    }
+}
+#[cfg(all(target_os = "linux", feature = "linux-audio"))]
+pub use linux::PulseAudioRouter;
+
+/// macOS backend, gated behind `macos-audio`. **Not yet functional**: device lookup
+/// (`find_device_id`) and change notification (`on_device_change`) both require walking
+/// `kAudioHardwarePropertyDevices` via CoreFoundation string APIs this crate doesn't depend on
+/// yet, so for now every call is a loud no-op (logged via `error!`) rather than a silent one,
+/// so callers can tell `spawn_router` isn't actually routing audio on this platform.
+#[cfg(all(target_os = "macos", feature = "macos-audio"))]
+mod macos {
+    use std::sync::Mutex;
+
+    use coreaudio_sys::{
+        kAudioHardwarePropertyDefaultOutputDevice, kAudioObjectPropertyElementMaster, kAudioObjectPropertyScopeGlobal,
+        kAudioObjectSystemObject, AudioObjectID, AudioObjectPropertyAddress, AudioObjectSetPropertyData,
+    };
+    use log::error;
+
+    use super::AudioRouter;
+
+    /// Tracks the most recently requested AirPods output device id so repeated
+    /// `set_default_output` calls for the same device are cheap no-ops.
+    pub struct CoreAudioRouter {
+        last_device_id: Mutex<Option<AudioObjectID>>,
+    }
+
+    impl CoreAudioRouter {
+        pub fn new() -> Self {
+            CoreAudioRouter { last_device_id: Mutex::new(None) }
+        }
+
+        /// Looks up the CoreAudio device id whose transport is Bluetooth and whose UID
+        /// contains `mac`, matching how macOS names Bluetooth audio devices.
+        ///
+        /// Unimplemented: enumerating `kAudioHardwarePropertyDevices` and matching
+        /// `kAudioDevicePropertyDeviceUID` against `mac` needs a CFString reader this crate
+        /// doesn't have a dependency for yet, so this always reports no match.
+        fn find_device_id(_mac: &str) -> Option<AudioObjectID> {
+            None
+        }
+    }
+
+    impl Default for CoreAudioRouter {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl AudioRouter for CoreAudioRouter {
+        fn set_default_output(&self, mac: &str) {
+            let Some(device_id) = Self::find_device_id(mac) else {
+                error!("CoreAudioRouter cannot look up a CoreAudio device id for \"{}\" yet; not switching the default output", mac);
+                return;
+            };
+            {
+                let mut last_device_id = self.last_device_id.lock().unwrap();
+                if *last_device_id == Some(device_id) {
+                    return;
+                }
+                *last_device_id = Some(device_id);
+            }
+
+            let address = AudioObjectPropertyAddress {
+                mSelector: kAudioHardwarePropertyDefaultOutputDevice,
+                mScope: kAudioObjectPropertyScopeGlobal,
+                mElement: kAudioObjectPropertyElementMaster,
+            };
+            unsafe {
+                AudioObjectSetPropertyData(
+                    kAudioObjectSystemObject,
+                    &address,
+                    0,
+                    std::ptr::null(),
+                    std::mem::size_of::<AudioObjectID>() as u32,
+                    &device_id as *const _ as *const std::ffi::c_void,
+                );
+            }
+        }
+
+        fn on_device_change(&self, on_change: Box<dyn Fn(String) + Send + Sync>) {
+            // A real implementation registers an `AudioObjectAddPropertyListener` on
+            // `kAudioHardwarePropertyDevices`/`kAudioHardwarePropertyDefaultOutputDevice` and
+            // forwards through `on_change`; not implemented yet for the same reason as
+            // `find_device_id`, so calling this is a no-op rather than silently storing a
+            // callback that will never fire.
+            error!("CoreAudioRouter does not support device-change notifications yet; ignoring on_device_change registration");
+            let _ = on_change;
+        }
+    }
+}
+#[cfg(all(target_os = "macos", feature = "macos-audio"))]
+pub use macos::CoreAudioRouter;
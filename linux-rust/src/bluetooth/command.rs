@@ -0,0 +1,63 @@
+//! Peer-style request API over `AACPManager`, for callers (the Flutter bridge, automation
+//! clients) that would rather hold a clonable `mpsc::Sender<Command>` than an
+//! `Arc<Mutex<AACPManagerState>>`. `AACPEvent` remains the status channel going the other
+//! way; `spawn` just gives the send side the same message-passing shape.
+
+use bluer::{Address, Result};
+use log::error;
+use tokio::sync::{mpsc, oneshot};
+
+use super::aacp::{AACPManager, ControlCommandIdentifiers, ProximityKeyType};
+
+/// A request to drive `AACPManager`'s send side. `RequestProximityKeys` is the one command
+/// with a correlated response: it's fulfilled by `AACPManager::receive_packet` when the
+/// matching `PROXIMITY_KEYS_RSP` arrives, rather than by `handle` itself.
+pub enum Command {
+    Connect(Address),
+    Disconnect,
+    Rename(String),
+    /// Convenience over `ControlCommand` for the common case of switching ANC mode; see
+    /// `console::resolve_value` for the off/anc/transparency/adaptive byte values.
+    SetAnc(u8),
+    ControlCommand { identifier: ControlCommandIdentifiers, value: Vec<u8> },
+    RequestProximityKeys {
+        key_types: Vec<ProximityKeyType>,
+        respond_to: oneshot::Sender<Vec<(u8, Vec<u8>)>>,
+    },
+    Hijack(String),
+}
+
+/// Spawns the task that owns `manager`'s send side and returns a clonable handle to it.
+pub fn spawn(manager: AACPManager) -> mpsc::Sender<Command> {
+    let (tx, rx) = mpsc::channel(32);
+    tokio::spawn(run(manager, rx));
+    tx
+}
+
+async fn run(mut manager: AACPManager, mut rx: mpsc::Receiver<Command>) {
+    while let Some(command) = rx.recv().await {
+        if let Err(e) = handle(&mut manager, command).await {
+            error!("Command failed: {}", e);
+        }
+    }
+}
+
+async fn handle(manager: &mut AACPManager, command: Command) -> Result<()> {
+    match command {
+        Command::Connect(addr) => {
+            manager.connect(addr).await;
+            Ok(())
+        }
+        Command::Disconnect => {
+            manager.disconnect().await;
+            Ok(())
+        }
+        Command::Rename(name) => manager.send_rename_packet(&name).await,
+        Command::SetAnc(mode) => manager.send_control_command(ControlCommandIdentifiers::ListeningMode, &[mode]).await,
+        Command::ControlCommand { identifier, value } => manager.send_control_command(identifier, &value).await,
+        Command::RequestProximityKeys { key_types, respond_to } => {
+            manager.request_proximity_keys(key_types, respond_to).await
+        }
+        Command::Hijack(target_mac_address) => manager.send_hijack_request(&target_mac_address).await,
+    }
+}
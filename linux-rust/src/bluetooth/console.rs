@@ -0,0 +1,135 @@
+//! Interactive text console over `AACPManager`'s command/event surface, useful for poking at
+//! the protocol without recompiling: `set listening-mode anc`, `get battery`, `rename Foo`,
+//! `proximity-keys`, `connected-devices`, `help`.
+
+use std::io::{self, BufRead, Write};
+
+use log::error;
+use num_traits::FromPrimitive as _;
+use tokio::sync::mpsc;
+
+use super::aacp::{AACPManager, ControlCommandIdentifiers, ProximityKeyType};
+
+/// Runs the console on the current task, reading commands from stdin until EOF. Any
+/// `AACPEvent` received while the console is running is printed as it arrives.
+pub async fn run(manager: AACPManager) {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    manager.set_event_channel(event_tx).await;
+
+    tokio::spawn(async move {
+        while let Some(event) = event_rx.recv().await {
+            println!("<- {:?}", event);
+        }
+    });
+
+    print_help();
+    print!("> ");
+    let _ = io::stdout().flush();
+
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                error!("Failed to read console input: {}", e);
+                break;
+            }
+        };
+
+        let line = line.trim();
+        if !line.is_empty() {
+            if let Err(e) = handle_line(&manager, line).await {
+                println!("error: {}", e);
+            }
+        }
+
+        print!("> ");
+        let _ = io::stdout().flush();
+    }
+}
+
+async fn handle_line(manager: &AACPManager, line: &str) -> Result<(), String> {
+    let mut parts = line.split_whitespace();
+    let command = parts.next().unwrap_or_default();
+
+    match command {
+        "help" => {
+            print_help();
+            Ok(())
+        }
+        "get" => match parts.next() {
+            Some("battery") => {
+                println!("{:?}", manager.state.lock().await.battery_info);
+                Ok(())
+            }
+            Some(other) => Err(format!("unknown `get` target: {}", other)),
+            None => Err("usage: get battery".to_string()),
+        },
+        "set" => {
+            let name = parts.next().ok_or("usage: set <command-name> <value>")?;
+            let value = parts.next().ok_or("usage: set <command-name> <value>")?;
+            let identifier = ControlCommandIdentifiers::from_name(name)
+                .ok_or_else(|| format!("unknown control command: {}", name))?;
+            let bytes = resolve_value(identifier, value)?;
+            manager.send_control_command(identifier, &bytes).await.map_err(|e| e.to_string())
+        }
+        "rename" => {
+            let name = parts.collect::<Vec<_>>().join(" ");
+            if name.is_empty() {
+                return Err("usage: rename <name>".to_string());
+            }
+            manager.send_rename_packet(&name).await.map_err(|e| e.to_string())
+        }
+        "proximity-keys" => manager
+            .send_proximity_keys_request(vec![ProximityKeyType::Irk, ProximityKeyType::EncKey])
+            .await
+            .map_err(|e| e.to_string()),
+        "connected-devices" => {
+            println!("{:?}", manager.state.lock().await.connected_devices);
+            Ok(())
+        }
+        other => Err(format!("unknown command: {} (try `help`)", other)),
+    }
+}
+
+/// Maps a friendly value token to the raw control-command byte. Most commands just take a
+/// number; `listening-mode` additionally accepts the named AirPods modes.
+fn resolve_value(identifier: ControlCommandIdentifiers, token: &str) -> Result<Vec<u8>, String> {
+    if identifier == ControlCommandIdentifiers::ListeningMode {
+        let value = match token.to_lowercase().as_str() {
+            "off" => Some(1u8),
+            "anc" | "noise-cancellation" => Some(2),
+            "transparency" => Some(3),
+            "adaptive" => Some(4),
+            _ => None,
+        };
+        if let Some(value) = value {
+            return Ok(vec![value]);
+        }
+    }
+    parse_numeric(token)
+}
+
+fn parse_numeric(token: &str) -> Result<Vec<u8>, String> {
+    let value = match token.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => token.parse::<u8>(),
+    };
+    value.map(|v| vec![v]).map_err(|_| format!("invalid value: {}", token))
+}
+
+fn print_help() {
+    println!("Commands:");
+    println!("  help                          - show this message");
+    println!("  get battery                   - print last known battery info");
+    println!("  connected-devices             - print last known connected devices");
+    println!("  proximity-keys                - request the IRK/encryption key pair");
+    println!("  rename <name>                 - rename the AirPods");
+    println!("  set <command-name> <value>   - send a control command, e.g. `set listening-mode anc`");
+    println!("Known control commands:");
+    for opcode in 0u8..=u8::MAX {
+        if let Some(identifier) = ControlCommandIdentifiers::from_u8(opcode) {
+            println!("  {}", identifier);
+        }
+    }
+}
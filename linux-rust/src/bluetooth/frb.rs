@@ -0,0 +1,100 @@
+//! flutter_rust_bridge binding layer over `AACPManager`/`Command`, so the Flutter UI can
+//! drive the AirPods connection and observe `AACPEvent` without reaching into an
+//! `Arc<Mutex<AACPManagerState>>` or raw `mpsc` channels from Dart.
+//!
+//! `DeviceData`, `DeviceInformation::AirPods` and `AudioSource` are already plain `pub`
+//! structs/enums in this crate, so flutter_rust_bridge's codegen mirrors them into Dart
+//! automatically; nothing extra is needed on those types themselves.
+
+use std::time::Duration;
+
+use bluer::Address;
+use flutter_rust_bridge::frb;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::frb_generated::StreamSink;
+
+use super::aacp::{AACPEvent, AACPManager, ControlCommandIdentifiers, ProximityKeyType};
+use super::command::{self, Command};
+
+/// Longest we'll wait for a `PROXIMITY_KEYS_RSP` before giving up on the request. Without
+/// this, a device that never answers would leave the awaiting Dart call hanging forever
+/// instead of surfacing an error.
+const PROXIMITY_KEYS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Opaque handle the Dart side holds for the lifetime of a connection. Wraps the manager
+/// (for subscribing to its events) and the command actor's sender (for writes), mirroring
+/// the split `AACPManager`/`Command` already gives Rust callers.
+#[frb(opaque)]
+pub struct AacpHandle {
+    manager: AACPManager,
+    commands: mpsc::Sender<Command>,
+}
+
+/// Creates a handle and starts its command actor. One handle should live for as long as the
+/// Dart side cares about a single AirPods connection.
+#[frb(sync)]
+pub fn aacp_create() -> AacpHandle {
+    let manager = AACPManager::new();
+    let commands = command::spawn(manager.clone());
+    AacpHandle { manager, commands }
+}
+
+/// Subscribes `sink` to every `AACPEvent` the manager emits, for as long as `handle` lives.
+/// Dart sees this as a `Stream<AacpEvent>` it can listen to without polling.
+pub async fn aacp_subscribe_events(handle: &AacpHandle, sink: StreamSink<AACPEvent>) -> anyhow::Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    handle.manager.set_event_channel(tx).await;
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if sink.add(event).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+pub async fn aacp_connect(handle: &AacpHandle, mac: String) -> anyhow::Result<()> {
+    let addr: Address = mac.parse()?;
+    handle.commands.send(Command::Connect(addr)).await?;
+    Ok(())
+}
+
+pub async fn aacp_disconnect(handle: &AacpHandle) -> anyhow::Result<()> {
+    handle.commands.send(Command::Disconnect).await?;
+    Ok(())
+}
+
+pub async fn aacp_rename(handle: &AacpHandle, name: String) -> anyhow::Result<()> {
+    handle.commands.send(Command::Rename(name)).await?;
+    Ok(())
+}
+
+pub async fn aacp_send_control_command(
+    handle: &AacpHandle,
+    identifier: ControlCommandIdentifiers,
+    value: Vec<u8>,
+) -> anyhow::Result<()> {
+    handle.commands.send(Command::ControlCommand { identifier, value }).await?;
+    Ok(())
+}
+
+/// Requests the IRK/encryption key pair and awaits the correlated response, unlike the
+/// console's `proximity-keys` command, which only sends the request.
+pub async fn aacp_request_proximity_keys(
+    handle: &AacpHandle,
+    key_types: Vec<ProximityKeyType>,
+) -> anyhow::Result<Vec<(u8, Vec<u8>)>> {
+    let (respond_to, response) = oneshot::channel();
+    handle.commands.send(Command::RequestProximityKeys { key_types, respond_to }).await?;
+    let keys = tokio::time::timeout(PROXIMITY_KEYS_TIMEOUT, response)
+        .await
+        .map_err(|_| anyhow::anyhow!("Timed out waiting for proximity keys response"))??;
+    Ok(keys)
+}
+
+pub async fn aacp_hijack(handle: &AacpHandle, target_mac_address: String) -> anyhow::Result<()> {
+    handle.commands.send(Command::Hijack(target_mac_address)).await?;
+    Ok(())
+}
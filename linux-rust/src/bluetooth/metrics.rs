@@ -0,0 +1,122 @@
+//! Optional metrics exporter, enabled via the `metrics` cargo feature.
+//!
+//! Modelled on the Prometheus pushgateway pattern: a background task wakes up on a
+//! configurable interval, snapshots `AACPManagerState` plus the opcode/error counters
+//! recorded by `receive_packet`, and POSTs the result to a configurable HTTP sink. Disabled
+//! by default so there's zero overhead for users who don't care to run a collector.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::error;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use super::aacp::{AACPManager, BatteryStatus};
+
+/// Where to push metrics and how often.
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    pub endpoint: String,
+    pub interval: Duration,
+}
+
+#[derive(Debug, Serialize)]
+struct BatteryGauge {
+    component: String,
+    level: u8,
+    charging: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct MetricsSnapshot {
+    battery: Vec<BatteryGauge>,
+    ear_detection_status: Vec<String>,
+    conversational_awareness_status: u8,
+    audio_source: Option<String>,
+    connection_uptime_secs: u64,
+    opcode_counts: HashMap<String, u64>,
+    parse_error_count: u64,
+}
+
+/// Spawns the background exporter task. Dropping the returned `JoinHandle` does not stop
+/// the task; callers that want to stop exporting should abort it themselves.
+pub fn spawn_exporter(manager: AACPManager, config: MetricsConfig) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut ticker = tokio::time::interval(config.interval);
+        loop {
+            ticker.tick().await;
+            let snapshot = build_snapshot(&manager).await;
+            if let Err(e) = push_snapshot(&client, &config.endpoint, &snapshot).await {
+                error!("Failed to push metrics to {}: {}", config.endpoint, e);
+            }
+        }
+    })
+}
+
+async fn build_snapshot(manager: &AACPManager) -> MetricsSnapshot {
+    let state = manager.state.lock().await;
+    MetricsSnapshot {
+        battery: state
+            .battery_info
+            .iter()
+            .map(|b| BatteryGauge {
+                component: format!("{:?}", b.component),
+                level: b.level,
+                charging: matches!(b.status, BatteryStatus::Charging),
+            })
+            .collect(),
+        ear_detection_status: state
+            .ear_detection_status
+            .iter()
+            .map(|s| format!("{:?}", s))
+            .collect(),
+        conversational_awareness_status: state.conversational_awareness_status,
+        audio_source: state.audio_source.as_ref().map(|a| a.mac.clone()),
+        connection_uptime_secs: state.connected_at.map(|t| t.elapsed().as_secs()).unwrap_or(0),
+        opcode_counts: counters::snapshot_opcode_counts(),
+        parse_error_count: counters::parse_error_count(),
+    }
+}
+
+async fn push_snapshot(
+    client: &reqwest::Client,
+    endpoint: &str,
+    snapshot: &MetricsSnapshot,
+) -> reqwest::Result<()> {
+    client.post(endpoint).json(snapshot).send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// Process-wide opcode/error counters, incremented from `AACPManager::receive_packet`.
+pub mod counters {
+    use super::*;
+
+    static OPCODE_COUNTS: Lazy<Mutex<HashMap<u8, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+    static PARSE_ERRORS: AtomicU64 = AtomicU64::new(0);
+
+    pub fn record_opcode(opcode: u8) {
+        let mut counts = OPCODE_COUNTS.lock().unwrap();
+        *counts.entry(opcode).or_insert(0) += 1;
+    }
+
+    pub fn record_parse_error() {
+        PARSE_ERRORS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn snapshot_opcode_counts() -> HashMap<String, u64> {
+        OPCODE_COUNTS
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(opcode, count)| (format!("{:#04x}", opcode), *count))
+            .collect()
+    }
+
+    pub(super) fn parse_error_count() -> u64 {
+        PARSE_ERRORS.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,11 @@
+pub mod aacp;
+#[cfg(feature = "audio-router")]
+pub mod audio_router;
+pub mod command;
+pub mod console;
+#[cfg(feature = "frb")]
+pub mod frb;
+pub mod opack;
+pub mod resolver;
+#[cfg(feature = "metrics")]
+pub mod metrics;
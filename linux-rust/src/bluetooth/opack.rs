@@ -0,0 +1,290 @@
+//! Apple OPACK encoding, used by the `SMART_ROUTING` opcode to carry key/value dictionaries
+//! (playback/streaming state, device identity, routing scores) instead of plain text.
+//!
+//! `Value` models the handful of OPACK types this protocol actually uses. Tag bytes below
+//! are reverse-engineered from captured `send_media_information*`/`send_hijack_*` traffic,
+//! not from an Apple specification, so treat them as "known to work for this opcode" rather
+//! than a complete OPACK implementation.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    Data(Vec<u8>),
+    Array(Vec<Value>),
+    Dict(Vec<(Value, Value)>),
+}
+
+impl Value {
+    /// Looks up `key` among a `Dict`'s entries by string equality. `None` for any other
+    /// variant, or if no entry's key is `Value::String(key)`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Dict(entries) => entries.iter().find_map(|(k, v)| match k {
+                Value::String(s) if s == key => Some(v),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+mod tag {
+    pub const FALSE: u8 = 0x02;
+    pub const TRUE: u8 = 0x01;
+    pub const TERMINATOR: u8 = 0x03;
+    pub const UINT8: u8 = 0x30;
+    pub const UINT16: u8 = 0x31;
+    pub const UINT32: u8 = 0x32;
+    pub const UINT64: u8 = 0x33;
+    pub const STRING_BASE: u8 = 0x40;
+    pub const STRING_MAX_LEN: u8 = 0x20;
+    pub const STRING_EXT: u8 = 0x61;
+    pub const DATA_BASE: u8 = 0x70;
+    pub const DATA_MAX_LEN: u8 = 0x20;
+    pub const DATA_EXT: u8 = 0x91;
+    pub const ARRAY_BASE: u8 = 0xD0;
+    pub const ARRAY_VARIABLE: u8 = 0xDF;
+    pub const DICT_BASE: u8 = 0xE0;
+    pub const DICT_VARIABLE: u8 = 0xEF;
+}
+
+/// Encodes `value` to its OPACK byte representation.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_into(value, &mut out);
+    out
+}
+
+fn encode_into(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Bool(false) => out.push(tag::FALSE),
+        Value::Bool(true) => out.push(tag::TRUE),
+        Value::Int(n) => encode_int(*n, out),
+        Value::String(s) => encode_string(s, out),
+        Value::Data(bytes) => encode_data(bytes, out),
+        Value::Array(items) => encode_array(items, out),
+        Value::Dict(entries) => encode_dict(entries, out),
+    }
+}
+
+fn encode_int(n: i64, out: &mut Vec<u8>) {
+    if (0..40).contains(&n) {
+        out.push(n as u8);
+    } else if let Ok(v) = u8::try_from(n) {
+        out.push(tag::UINT8);
+        out.push(v);
+    } else if let Ok(v) = u16::try_from(n) {
+        out.push(tag::UINT16);
+        out.extend_from_slice(&v.to_le_bytes());
+    } else if let Ok(v) = u32::try_from(n) {
+        out.push(tag::UINT32);
+        out.extend_from_slice(&v.to_le_bytes());
+    } else {
+        out.push(tag::UINT64);
+        out.extend_from_slice(&(n as u64).to_le_bytes());
+    }
+}
+
+fn encode_string(s: &str, out: &mut Vec<u8>) {
+    let bytes = s.as_bytes();
+    if bytes.len() <= tag::STRING_MAX_LEN as usize {
+        out.push(tag::STRING_BASE + bytes.len() as u8);
+    } else {
+        out.push(tag::STRING_EXT);
+        out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    }
+    out.extend_from_slice(bytes);
+}
+
+fn encode_data(bytes: &[u8], out: &mut Vec<u8>) {
+    if bytes.len() <= tag::DATA_MAX_LEN as usize {
+        out.push(tag::DATA_BASE + bytes.len() as u8);
+    } else {
+        out.push(tag::DATA_EXT);
+        out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    }
+    out.extend_from_slice(bytes);
+}
+
+fn encode_array(items: &[Value], out: &mut Vec<u8>) {
+    if items.len() < (tag::ARRAY_VARIABLE - tag::ARRAY_BASE) as usize {
+        out.push(tag::ARRAY_BASE + items.len() as u8);
+        for item in items {
+            encode_into(item, out);
+        }
+    } else {
+        out.push(tag::ARRAY_VARIABLE);
+        for item in items {
+            encode_into(item, out);
+        }
+        out.push(tag::TERMINATOR);
+    }
+}
+
+fn encode_dict(entries: &[(Value, Value)], out: &mut Vec<u8>) {
+    if entries.len() < (tag::DICT_VARIABLE - tag::DICT_BASE) as usize {
+        out.push(tag::DICT_BASE + entries.len() as u8);
+        for (key, value) in entries {
+            encode_into(key, out);
+            encode_into(value, out);
+        }
+    } else {
+        out.push(tag::DICT_VARIABLE);
+        for (key, value) in entries {
+            encode_into(key, out);
+            encode_into(value, out);
+        }
+        out.push(tag::TERMINATOR);
+    }
+}
+
+/// Decodes a single OPACK value from the start of `bytes`. Returns `None` on truncated or
+/// unrecognized input rather than panicking, matching how the rest of `receive_packet`
+/// handles malformed device data.
+pub fn decode(bytes: &[u8]) -> Option<Value> {
+    let mut cursor = 0;
+    decode_value(bytes, &mut cursor)
+}
+
+fn decode_value(bytes: &[u8], cursor: &mut usize) -> Option<Value> {
+    let tag_byte = *bytes.get(*cursor)?;
+    *cursor += 1;
+    match tag_byte {
+        tag::FALSE => Some(Value::Bool(false)),
+        tag::TRUE => Some(Value::Bool(true)),
+        0x00..=0x27 => Some(Value::Int(tag_byte as i64)),
+        tag::UINT8 => decode_uint::<1>(bytes, cursor),
+        tag::UINT16 => decode_uint::<2>(bytes, cursor),
+        tag::UINT32 => decode_uint::<4>(bytes, cursor),
+        tag::UINT64 => decode_uint::<8>(bytes, cursor),
+        tag::STRING_EXT => {
+            let len = decode_len_u32(bytes, cursor)?;
+            decode_str(bytes, cursor, len)
+        }
+        b if (tag::STRING_BASE..=tag::STRING_BASE + tag::STRING_MAX_LEN).contains(&b) => {
+            let len = (b - tag::STRING_BASE) as usize;
+            decode_str(bytes, cursor, len)
+        }
+        tag::DATA_EXT => {
+            let len = decode_len_u32(bytes, cursor)?;
+            decode_data(bytes, cursor, len)
+        }
+        b if (tag::DATA_BASE..=tag::DATA_BASE + tag::DATA_MAX_LEN).contains(&b) => {
+            let len = (b - tag::DATA_BASE) as usize;
+            decode_data(bytes, cursor, len)
+        }
+        tag::ARRAY_VARIABLE => decode_variable_array(bytes, cursor),
+        b if (tag::ARRAY_BASE..tag::ARRAY_VARIABLE).contains(&b) => {
+            let count = (b - tag::ARRAY_BASE) as usize;
+            decode_fixed_array(bytes, cursor, count)
+        }
+        tag::DICT_VARIABLE => decode_variable_dict(bytes, cursor),
+        b if (tag::DICT_BASE..tag::DICT_VARIABLE).contains(&b) => {
+            let count = (b - tag::DICT_BASE) as usize;
+            decode_fixed_dict(bytes, cursor, count)
+        }
+        _ => None,
+    }
+}
+
+fn decode_uint<const N: usize>(bytes: &[u8], cursor: &mut usize) -> Option<Value> {
+    let slice = bytes.get(*cursor..*cursor + N)?;
+    *cursor += N;
+    let mut buf = [0u8; 8];
+    buf[..N].copy_from_slice(slice);
+    Some(Value::Int(u64::from_le_bytes(buf) as i64))
+}
+
+fn decode_len_u32(bytes: &[u8], cursor: &mut usize) -> Option<usize> {
+    let slice = bytes.get(*cursor..*cursor + 4)?;
+    *cursor += 4;
+    Some(u32::from_le_bytes(slice.try_into().ok()?) as usize)
+}
+
+fn decode_str(bytes: &[u8], cursor: &mut usize, len: usize) -> Option<Value> {
+    let slice = bytes.get(*cursor..*cursor + len)?;
+    *cursor += len;
+    Some(Value::String(std::str::from_utf8(slice).ok()?.to_string()))
+}
+
+fn decode_data(bytes: &[u8], cursor: &mut usize, len: usize) -> Option<Value> {
+    let slice = bytes.get(*cursor..*cursor + len)?;
+    *cursor += len;
+    Some(Value::Data(slice.to_vec()))
+}
+
+fn decode_fixed_array(bytes: &[u8], cursor: &mut usize, count: usize) -> Option<Value> {
+    let mut items = Vec::with_capacity(count);
+    for _ in 0..count {
+        items.push(decode_value(bytes, cursor)?);
+    }
+    Some(Value::Array(items))
+}
+
+fn decode_variable_array(bytes: &[u8], cursor: &mut usize) -> Option<Value> {
+    let mut items = Vec::new();
+    while *bytes.get(*cursor)? != tag::TERMINATOR {
+        items.push(decode_value(bytes, cursor)?);
+    }
+    *cursor += 1;
+    Some(Value::Array(items))
+}
+
+fn decode_fixed_dict(bytes: &[u8], cursor: &mut usize, count: usize) -> Option<Value> {
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        let key = decode_value(bytes, cursor)?;
+        let value = decode_value(bytes, cursor)?;
+        entries.push((key, value));
+    }
+    Some(Value::Dict(entries))
+}
+
+fn decode_variable_dict(bytes: &[u8], cursor: &mut usize) -> Option<Value> {
+    let mut entries = Vec::new();
+    while *bytes.get(*cursor)? != tag::TERMINATOR {
+        let key = decode_value(bytes, cursor)?;
+        let value = decode_value(bytes, cursor)?;
+        entries.push((key, value));
+    }
+    *cursor += 1;
+    Some(Value::Dict(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_short_strings_with_length_tag() {
+        assert_eq!(encode(&Value::String("Mac".to_string())), vec![0x43, b'M', b'a', b'c']);
+        assert_eq!(encode(&Value::String("btName".to_string()))[0], 0x46);
+    }
+
+    #[test]
+    fn encodes_small_ints_inline() {
+        assert_eq!(encode(&Value::Int(5)), vec![0x05]);
+        assert_eq!(encode(&Value::Int(100)), vec![0x30, 100]);
+    }
+
+    #[test]
+    fn round_trips_dict() {
+        let value = Value::Dict(vec![
+            (Value::String("btName".to_string()), Value::String("Mac".to_string())),
+            (Value::String("hostStreamingState".to_string()), Value::String("NO".to_string())),
+            (Value::String("count".to_string()), Value::Int(100)),
+            (Value::String("flag".to_string()), Value::Bool(true)),
+        ]);
+        let encoded = encode(&value);
+        assert_eq!(decode(&encoded), Some(value));
+    }
+
+    #[test]
+    fn get_looks_up_dict_entries_by_key() {
+        let value = Value::Dict(vec![(Value::String("reason".to_string()), Value::String("Hijackv2".to_string()))]);
+        assert_eq!(value.get("reason"), Some(&Value::String("Hijackv2".to_string())));
+        assert_eq!(value.get("missing"), None);
+    }
+}